@@ -0,0 +1,60 @@
+//! Pluggable snapshot exporters for `Registry::flush_every`.
+
+use std::{
+    fmt::Debug,
+    sync::{Arc, Condvar, Mutex},
+    thread::JoinHandle,
+};
+
+/// Receives periodic snapshots of a `Registry`'s counts. See `Registry::flush_every`.
+pub trait Exporter<Id> {
+    fn export(&self, snapshot: &[(Id, usize)]);
+}
+
+impl<Id, F> Exporter<Id> for F
+where
+    F: Fn(&[(Id, usize)]),
+{
+    fn export(&self, snapshot: &[(Id, usize)]) {
+        self(snapshot)
+    }
+}
+
+/// Prints each snapshot to stdout. Handy for quick visibility without wiring up a real metrics
+/// pipeline; swap in your own `Exporter` (or a closure) to ship counts anywhere else.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdoutExporter;
+
+impl<Id> Exporter<Id> for StdoutExporter
+where
+    Id: Debug,
+{
+    fn export(&self, snapshot: &[(Id, usize)]) {
+        println!("{snapshot:?}");
+    }
+}
+
+/// Stops the background flush loop spawned by `Registry::flush_every` when dropped, after
+/// performing one final flush.
+pub struct FlushGuard {
+    pub(crate) stop: Arc<(Mutex<bool>, Condvar)>,
+    pub(crate) handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        let (lock, condition) = &*self.stop;
+        *lock.lock().expect("local lock") = true;
+        condition.notify_one();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Debug for FlushGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlushGuard").finish()
+    }
+}
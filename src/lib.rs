@@ -72,6 +72,41 @@
 //! assert_eq!(vec![(MyCategories::Specific, 0)], registry.read_counts::<Vec<_>>(), "both dropped");
 //! ```
 //!
+//! Dynamic-id registries (keyed per-user, per-connection, etc.) can grow without bound if nothing
+//! ever forgets about a category. You can opt in to evicting categories that are both empty and
+//! have gone untouched for a while:
+//! ```rust
+//! use resourcetrack::new_registry;
+//! use std::{sync::Arc, time::Duration};
+//!
+//! let registry = new_registry::<Arc<String>>();
+//! let user_tracker = registry.category(Arc::new("user-1".to_string()));
+//! drop(user_tracker.track());
+//!
+//! // Nothing is holding the category open, so it's eligible for eviction immediately.
+//! registry.evict_idle(Duration::ZERO);
+//! assert_eq!(Vec::<(Arc<String>, usize)>::new(), registry.read_counts::<Vec<_>>());
+//!
+//! // The Tracker you already hold still works -- it transparently re-registers its category.
+//! let _counter = user_tracker.track();
+//! assert_eq!(vec![(Arc::new("user-1".to_string()), 1)], registry.read_counts::<Vec<_>>());
+//! ```
+//!
+//! Eviction and re-registration happen transparently, but they mean a long-lived `Tracker` can
+//! end up pointing at a category that was reset underneath it. Every `Count`/`Size` remembers
+//! the generation of the category it was created against, so you can tell whether a guard still
+//! counts against the currently-live category:
+//! ```rust
+//! use resourcetrack::new_registry;
+//! use std::sync::Arc;
+//!
+//! let registry = new_registry::<Arc<String>>();
+//! let user_tracker = registry.category(Arc::new("user-1".to_string()));
+//!
+//! let counter = user_tracker.track();
+//! assert!(counter.is_current(&user_tracker), "freshly tracked against the live generation");
+//! ```
+//!
 //! You can track sized resources, where their size changes. To stay sane, you should probably limit
 //! yourself to either using track() or track_sized() for a given category. You can mix counts and sizes
 //! within a registry though, no problem!
@@ -120,16 +155,116 @@
 //!     counts,
 //! )
 //! ```
+//!
+//! If you'd rather not hand-roll a polling loop around `read_counts`, plug in an `Exporter` and
+//! `Registry::flush_every` will run it on a background thread for you:
+//! ```rust
+//! use resourcetrack::{export::Exporter, new_registry};
+//! use std::{
+//!     sync::{Arc, Mutex},
+//!     time::Duration,
+//! };
+//!
+//! #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+//! enum MyCategories {
+//!     Specific,
+//! }
+//!
+//! let registry = new_registry::<MyCategories>();
+//! let _counter = registry.category(MyCategories::Specific).track();
+//!
+//! let flushes = Arc::new(Mutex::new(Vec::new()));
+//! let recorder = flushes.clone();
+//! let guard = registry.flush_every(Duration::from_secs(3600), move |snapshot: &[(MyCategories, usize)]| {
+//!     recorder.lock().expect("local lock").push(snapshot.to_vec());
+//! });
+//!
+//! drop(guard); // stops the background thread, but not before one final flush
+//! assert_eq!(1, flushes.lock().expect("local lock").len());
+//! ```
+//!
+//! A running total hides transient spikes. For a size category, you often also want the peak
+//! value it ever held - read it alongside the current total, and reset the high-water-mark
+//! whenever you want to start observing "peak since last poll":
+//! ```rust
+//! use resourcetrack::{new_registry, tracked};
+//!
+//! #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+//! enum MyCategories {
+//!     Buffers,
+//! }
+//!
+//! let registry = new_registry::<MyCategories>();
+//! let buffers = registry.category(MyCategories::Buffers);
+//!
+//! let mut size: tracked::Size = buffers.track_size(0);
+//! size.add(100);
+//! size.subtract(60);
+//!
+//! assert_eq!(vec![(MyCategories::Buffers, 40, 100)], registry.read_stats::<Vec<_>>());
+//!
+//! registry.reset_peaks();
+//! assert_eq!(vec![(MyCategories::Buffers, 40)], registry.read_peaks::<Vec<_>>());
+//! ```
 
 use std::{
     borrow::Borrow,
     collections::HashMap,
     fmt::Debug,
-    sync::{atomic::AtomicUsize, Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize},
+        Arc, Condvar, Mutex, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+pub mod export;
+
+/// Milliseconds since the unix epoch, used as a cheap monotonic-enough "last touched" stamp.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock")
+        .as_millis() as u64
+}
+
 pub struct Registry<Id> {
-    categories: Mutex<HashMap<Id, Category>>,
+    categories: Arc<RwLock<HashMap<Id, Category>>>,
+    /// The most recently handed out generation per id, kept independently of `categories` so
+    /// that generation numbers stay monotonic across an eviction/re-registration cycle no matter
+    /// which caller (a fresh `category()` lookup, or a stale `Tracker` re-registering) happens
+    /// to recreate the slot. Unlike `categories`, entries here are never removed - eviction
+    /// only bounds the cost of a live `Category` (its atomics), not this per-id bookkeeping (a
+    /// clone of the id plus a `u64`).
+    generations: Arc<Mutex<HashMap<Id, u64>>>,
+}
+
+/// Build a brand new `Category` for `id`, assigning it the generation after the one last recorded
+/// for that id (0 for an id never seen before).
+/// Shared by `Registry::category`'s first-time-registration path and `Tracker::ensure_registered`'s
+/// re-registration path, so both ways of recreating a category agree on its generation.
+fn fresh_category<Id>(generations: &Mutex<HashMap<Id, u64>>, id: &Id) -> Category
+where
+    Id: Eq + std::hash::Hash + Clone,
+{
+    let mut generations = generations.lock().expect("local lock");
+    let generation = match generations.get_mut(id) {
+        Some(next) => {
+            *next += 1;
+            *next
+        }
+        None => {
+            generations.insert(id.clone(), 0);
+            0
+        }
+    };
+    Category {
+        total: Arc::new(AtomicUsize::new(0)),
+        peak: Arc::new(AtomicUsize::new(0)),
+        last_touched_millis: Arc::new(AtomicU64::new(now_millis())),
+        registered: Arc::new(AtomicBool::new(true)),
+        generation,
+    }
 }
 
 impl<Id> Debug for Registry<Id>
@@ -138,7 +273,7 @@ where
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Registry")
-            .field("categories", &self.categories.lock().expect("local mutex"))
+            .field("categories", &self.categories.read().expect("local lock"))
             .finish()
     }
 }
@@ -155,6 +290,7 @@ where
 {
     Registry {
         categories: Default::default(),
+        generations: Default::default(),
     }
 }
 
@@ -162,57 +298,237 @@ impl<Id> Registry<Id>
 where
     Id: Debug + Eq + std::hash::Hash + Clone,
 {
-    /// You should cache the tracker. Getting a reference requires a mutex interaction.
-    /// It's fine to do it occasionally, or in non-latency-sensitive paths, but this is
-    /// not an optimized path. Tracker and Count are quick.
-    pub fn category<Name>(&self, name: Name) -> Tracker
+    /// You should cache the tracker. Looking one up always takes at least a shared read lock, so
+    /// it waits out any exclusive lock held elsewhere (`evict_idle`'s removal pass, or a `Tracker`
+    /// re-registering an evicted category). The first lookup of a given id also takes a brief
+    /// exclusive lock to create it, which blocks concurrent `read_counts` and `evict_idle`
+    /// callers in turn. A fully lock-free map (e.g. a persistent/immutable
+    /// map, or an atomically-swapped snapshot) would close that gap entirely; this crate only
+    /// depends on std and doesn't use `unsafe`, though, and neither is available in safe std
+    /// alone. `RwLock` is what's shipped here, but that's an open trade-off to weigh against the
+    /// std-only/no-`unsafe` constraint, not a settled design decision - flagging it rather than
+    /// treating the gap as closed. It's fine to look categories up occasionally, or in
+    /// non-latency-sensitive paths, but this is not an optimized path. Tracker and Count are
+    /// quick.
+    pub fn category<Name>(&self, name: Name) -> Tracker<Id>
     where
         Name: Into<Id> + std::hash::Hash + std::cmp::Eq,
         Id: Borrow<Name>,
     {
-        let mut categories = self.categories.lock().expect("local mutex");
-        let count = match categories.get(&name) {
-            Some(existing) => existing.total.clone(),
+        if let Some((id, category)) = self
+            .categories
+            .read()
+            .expect("local lock")
+            .get_key_value(&name)
+        {
+            return self.tracker_for(id.clone(), category.clone());
+        }
+
+        let mut categories = self.categories.write().expect("local lock");
+        let (id, category) = match categories.get_key_value(&name) {
+            Some((id, existing)) => (id.clone(), existing.clone()),
             None => {
-                let count = Arc::new(AtomicUsize::new(0));
-                categories.insert(
-                    name.into(),
-                    Category {
-                        total: count.clone(),
-                    },
-                );
-                count
+                let id: Id = name.into();
+                let category = fresh_category(&self.generations, &id);
+                categories.insert(id.clone(), category.clone());
+                (id, category)
             }
         };
-        Tracker { count }
+        drop(categories);
+        self.tracker_for(id, category)
+    }
+
+    fn tracker_for(&self, id: Id, category: Category) -> Tracker<Id> {
+        Tracker {
+            id,
+            view: Arc::new(Mutex::new(category)),
+            categories: self.categories.clone(),
+            generations: self.generations.clone(),
+        }
     }
 
     /// This is appropriate for infrequent access - e.g., for polling metrics every few seconds.
     /// It walks the categories and loads counts. Consider reading into a vector instead of a map.
     ///
-    /// This function contends with category(). Try to get your category trackers up front and use
-    /// this only in a background job.
+    /// This only takes a shared read lock, so it no longer contends with `category()` calls for
+    /// already-registered categories, nor with `evict_idle`'s scan (see `evict_idle`) - only with
+    /// the rare write lock taken to register a brand new category (or to re-register an evicted
+    /// one through a surviving `Tracker`), or with `evict_idle`'s brief
+    /// removal pass once it has found idle categories. Try to get your category trackers up
+    /// front regardless; this still isn't an optimized path.
     pub fn read_counts<AsCollection>(&self) -> AsCollection
     where
         AsCollection: FromIterator<(Id, usize)>,
     {
-        let categories = self.categories.lock().expect("local mutex");
+        let categories = self.categories.read().expect("local lock");
         categories
             .iter()
             .map(|(id, category)| (id.clone(), category.total()))
             .collect()
     }
+
+    /// Like `read_counts`, but reads each category's high-water-mark instead of its current
+    /// total. See `tracked::Size::add`/`set` for how the peak is maintained, and `reset_peaks`
+    /// for observing "peak since last poll".
+    pub fn read_peaks<AsCollection>(&self) -> AsCollection
+    where
+        AsCollection: FromIterator<(Id, usize)>,
+    {
+        let categories = self.categories.read().expect("local lock");
+        categories
+            .iter()
+            .map(|(id, category)| (id.clone(), category.peak()))
+            .collect()
+    }
+
+    /// Reads both the current total and the high-water-mark for every category in one pass.
+    pub fn read_stats<AsCollection>(&self) -> AsCollection
+    where
+        AsCollection: FromIterator<(Id, usize, usize)>,
+    {
+        let categories = self.categories.read().expect("local lock");
+        categories
+            .iter()
+            .map(|(id, category)| (id.clone(), category.total(), category.peak()))
+            .collect()
+    }
+
+    /// Reset every category's high-water-mark down to its current total. The running total
+    /// stays authoritative and untouched; this only rebases what counts as "peak" going
+    /// forward, so a metrics loop can observe peak-since-last-poll.
+    pub fn reset_peaks(&self) {
+        let categories = self.categories.read().expect("local lock");
+        for category in categories.values() {
+            category
+                .peak
+                .store(category.total(), std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    /// Forget about categories that are both empty (`total == 0`) and haven't been touched -
+    /// via `track`, `track_size`, `set`, `add`, or `subtract` - within `older_than`. This is
+    /// meant for dynamic-id registries (e.g. per-user categories) that would otherwise grow
+    /// without bound.
+    ///
+    /// A category that still holds a non-zero total is never evicted, even if it's old, so
+    /// in-flight resources can never be undercounted by this call.
+    ///
+    /// This bounds the cost of a live category's atomics, not the registry's memory use overall:
+    /// each id's generation counter (see `Tracker::generation`) is kept forever so it stays
+    /// monotonic across repeated eviction/re-registration cycles. That bookkeeping is a clone of
+    /// the id plus a `u64` per id ever seen, cheaper than a live category's `Arc`s, but it does mean an
+    /// unbounded stream of one-off ids still grows the registry slowly over time.
+    ///
+    /// Eviction only removes the registry's map entry for the category. Any `Tracker` (or
+    /// `tracked::Count` / `tracked::Size`) you still hold keeps working, and the next
+    /// `track`/`track_size` call made through a surviving `Tracker` transparently re-registers
+    /// its category under the same id.
+    ///
+    /// Deciding which categories are idle only takes a shared read lock, so it never blocks
+    /// `category()`/`read_counts` callers, no matter how large the registry is. Only the removal
+    /// of the categories that actually turned out to be idle takes a brief exclusive lock, and
+    /// that critical section is just a handful of map removals, not a scan of the whole registry.
+    pub fn evict_idle(&self, older_than: Duration) {
+        let now = now_millis();
+        let threshold_millis = older_than.as_millis() as u64;
+        let is_idle = |category: &Category| {
+            let idle_for = now.saturating_sub(
+                category
+                    .last_touched_millis
+                    .load(std::sync::atomic::Ordering::Relaxed),
+            );
+            category.total() == 0 && idle_for >= threshold_millis
+        };
+
+        let idle_ids: Vec<Id> = self
+            .categories
+            .read()
+            .expect("local lock")
+            .iter()
+            .filter(|(_, category)| is_idle(category))
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        if idle_ids.is_empty() {
+            return;
+        }
+
+        let mut categories = self.categories.write().expect("local lock");
+        for id in idle_ids {
+            // Re-check under the exclusive lock: the category may have picked up a count, or
+            // been touched again, since the read-locked scan above decided it was idle.
+            if let std::collections::hash_map::Entry::Occupied(entry) = categories.entry(id) {
+                if is_idle(entry.get()) {
+                    entry
+                        .get()
+                        .registered
+                        .store(false, std::sync::atomic::Ordering::Relaxed);
+                    entry.remove();
+                }
+            }
+        }
+    }
+}
+
+impl<Id> Registry<Id>
+where
+    Id: Debug + Eq + std::hash::Hash + Clone + Send + Sync + 'static,
+{
+    /// Spawn a background thread that calls `read_counts` every `period` and hands the
+    /// resulting snapshot to `exporter`. Returns a guard: dropping it stops the thread, but not
+    /// before it performs one final flush, so the last readings before shutdown aren't lost.
+    pub fn flush_every<E>(&self, period: Duration, exporter: E) -> export::FlushGuard
+    where
+        E: export::Exporter<Id> + Send + 'static,
+    {
+        let categories = self.categories.clone();
+        let generations = self.generations.clone();
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_stop = stop.clone();
+        let handle = std::thread::spawn(move || {
+            let registry = Registry {
+                categories,
+                generations,
+            };
+            loop {
+                let (lock, condition) = &*thread_stop;
+                let stopped = lock.lock().expect("local lock");
+                let (stopped, _timed_out) = condition
+                    .wait_timeout_while(stopped, period, |stopped| !*stopped)
+                    .expect("local lock");
+                let should_stop = *stopped;
+                drop(stopped);
+
+                exporter.export(&registry.read_counts::<Vec<_>>());
+
+                if should_stop {
+                    break;
+                }
+            }
+        });
+        export::FlushGuard {
+            stop,
+            handle: Some(handle),
+        }
+    }
 }
 
 #[derive(Clone)]
 struct Category {
     total: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+    last_touched_millis: Arc<AtomicU64>,
+    registered: Arc<AtomicBool>,
+    /// Fixed for this `Category`'s lifetime - see `Registry::generations`/`fresh_category` for
+    /// how it's assigned.
+    generation: u64,
 }
 
 impl Debug for Category {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Category")
             .field("total", &self.total())
+            .field("peak", &self.peak())
             .finish()
     }
 }
@@ -221,30 +537,93 @@ impl Category {
     pub fn total(&self) -> usize {
         self.total.load(std::sync::atomic::Ordering::Relaxed)
     }
+
+    pub fn peak(&self) -> usize {
+        self.peak.load(std::sync::atomic::Ordering::Relaxed)
+    }
 }
 
 #[derive(Clone)]
-pub struct Tracker {
-    count: Arc<AtomicUsize>,
+pub struct Tracker<Id> {
+    id: Id,
+    /// This `Tracker`'s current view of its category. It's shared only by clones of this
+    /// `Tracker`, not with `Tracker`s from separate `Registry::category` lookups for the same id,
+    /// so that `ensure_registered` can swap it out wholesale when it
+    /// discovers the registry already has a different, concurrently-created entry for this id -
+    /// see `ensure_registered` for why that matters.
+    view: Arc<Mutex<Category>>,
+    categories: Arc<RwLock<HashMap<Id, Category>>>,
+    generations: Arc<Mutex<HashMap<Id, u64>>>,
 }
 
-impl Debug for Tracker {
+impl<Id> Debug for Tracker<Id> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.count.load(std::sync::atomic::Ordering::Relaxed)
-        )
+        write!(f, "{}", self.view.lock().expect("local lock").total())
     }
 }
 
-impl Tracker {
+impl<Id> Tracker<Id>
+where
+    Id: Debug + Eq + std::hash::Hash + Clone,
+{
+    /// The category this `Tracker` currently refers to.
+    fn current(&self) -> Category {
+        self.view.lock().expect("local lock").clone()
+    }
+
+    /// Stamp this category as touched just now.
+    fn touch(&self, view: &Category) {
+        view.last_touched_millis
+            .store(now_millis(), std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// If `Registry::evict_idle` dropped this category's map entry out from under us, put it
+    /// back. The common case is a lock of this `Tracker`'s own view plus a single relaxed atomic
+    /// load, so this stays cheap even though eviction is possible.
+    ///
+    /// Another `Tracker` for the same id may have already re-registered the category (e.g. via
+    /// `Registry::category`) between the eviction and this call. If so, `or_insert_with`'s
+    /// closure never runs - the entry is already there - and we must adopt that entry as our own
+    /// view rather than keep incrementing Arcs nothing else can see. Adopting the winner (instead
+    /// of overwriting it with our own stale Arcs) is what lets two independent `Tracker`s for the
+    /// same id converge back onto a single counter after an eviction. If `or_insert_with`'s
+    /// closure does run, it goes through `fresh_category` too, so the new category's generation
+    /// comes from `Registry::generations` rather than anything tracker-local - see there for why.
+    fn ensure_registered(&self) {
+        let mut view = self.view.lock().expect("local lock");
+        if view
+            .registered
+            .load(std::sync::atomic::Ordering::Relaxed)
+        {
+            return;
+        }
+        let mut categories = self.categories.write().expect("local lock");
+        let current = categories
+            .entry(self.id.clone())
+            .or_insert_with(|| fresh_category(&self.generations, &self.id))
+            .clone();
+        drop(categories);
+        *view = current;
+    }
+
+    /// The generation of the category this `Tracker` currently refers to. Bumped every time
+    /// `Registry::evict_idle` removes and a later `track`/`track_size` call re-creates the
+    /// category's slot, so you can tell a freshly tracked guard apart from one superseded by
+    /// an eviction/re-registration cycle.
+    pub fn generation(&self) -> u64 {
+        self.current().generation
+    }
+
     /// Hold 1 count against the category until the returned tracked::Count guard is dropped.
     pub fn track(&self) -> tracked::Count {
-        self.count
+        self.ensure_registered();
+        let view = self.current();
+        self.touch(&view);
+        view.total
             .fetch_add(1, std::sync::atomic::Ordering::Release);
         tracked::Count {
-            total: self.count.clone(),
+            total: view.total.clone(),
+            generation: view.generation,
         }
     }
 
@@ -253,11 +632,20 @@ impl Tracker {
     /// resource category: When you change the buffer size you can also update the tracked::Size
     /// for better visibility into where your memory is spent.
     pub fn track_size(&self, initial: usize) -> tracked::Size {
-        self.count
+        self.ensure_registered();
+        let view = self.current();
+        self.touch(&view);
+        let previous = view
+            .total
             .fetch_add(initial, std::sync::atomic::Ordering::Release);
+        view.peak
+            .fetch_max(previous + initial, std::sync::atomic::Ordering::Relaxed);
         tracked::Size {
-            total: self.count.clone(),
+            total: view.total.clone(),
+            peak: view.peak.clone(),
             local: initial,
+            last_touched_millis: view.last_touched_millis.clone(),
+            generation: view.generation,
         }
     }
 }
@@ -265,12 +653,16 @@ impl Tracker {
 pub mod tracked {
     use std::{
         fmt::Debug,
-        sync::{atomic::AtomicUsize, Arc},
+        sync::{
+            atomic::{AtomicU64, AtomicUsize},
+            Arc,
+        },
     };
 
     /// Fixed handle for a resource that is only counted by its existence.
     pub struct Count {
         pub(crate) total: Arc<AtomicUsize>,
+        pub(crate) generation: u64,
     }
 
     impl Debug for Count {
@@ -283,6 +675,18 @@ pub mod tracked {
         }
     }
 
+    impl Count {
+        /// Whether this guard was created against the category's currently-live generation,
+        /// i.e. `tracker` hasn't been re-registered (via `Registry::evict_idle` followed by a
+        /// `track`/`track_size` call) since this guard was created.
+        pub fn is_current<Id>(&self, tracker: &crate::Tracker<Id>) -> bool
+        where
+            Id: Debug + Eq + std::hash::Hash + Clone,
+        {
+            self.generation == tracker.generation()
+        }
+    }
+
     impl Drop for Count {
         fn drop(&mut self) {
             self.total
@@ -293,7 +697,10 @@ pub mod tracked {
     /// Mutable handle for a resource of changing size.
     pub struct Size {
         pub(crate) total: Arc<AtomicUsize>,
+        pub(crate) peak: Arc<AtomicUsize>,
         pub(crate) local: usize,
+        pub(crate) last_touched_millis: Arc<AtomicU64>,
+        pub(crate) generation: u64,
     }
 
     impl Debug for Size {
@@ -309,6 +716,29 @@ pub mod tracked {
     }
 
     impl Size {
+        /// Whether this guard was created against the category's currently-live generation -
+        /// see `Count::is_current` for details.
+        pub fn is_current<Id>(&self, tracker: &crate::Tracker<Id>) -> bool
+        where
+            Id: Debug + Eq + std::hash::Hash + Clone,
+        {
+            self.generation == tracker.generation()
+        }
+
+        /// Stamp this category as touched just now - see `Registry::evict_idle`.
+        fn touch(&self) {
+            self.last_touched_millis
+                .store(crate::now_millis(), std::sync::atomic::Ordering::Relaxed);
+        }
+
+        /// Raise the category's high-water-mark to its current total, if it isn't already
+        /// there - see `Registry::read_peaks`.
+        fn touch_peak(&self) {
+            let current = self.total.load(std::sync::atomic::Ordering::Relaxed);
+            self.peak
+                .fetch_max(current, std::sync::atomic::Ordering::Relaxed);
+        }
+
         /// change the tracked count for this resource
         pub fn set(&mut self, new_size: usize) {
             let difference = new_size.abs_diff(self.local);
@@ -320,6 +750,8 @@ pub mod tracked {
                     .fetch_add(difference, std::sync::atomic::Ordering::Release);
             }
             self.local = new_size;
+            self.touch();
+            self.touch_peak();
         }
 
         /// change the tracked count for this resource
@@ -327,6 +759,8 @@ pub mod tracked {
             self.total
                 .fetch_add(amount, std::sync::atomic::Ordering::Release);
             self.local += amount;
+            self.touch();
+            self.touch_peak();
         }
 
         /// change the tracked count for this resource
@@ -336,6 +770,7 @@ pub mod tracked {
                 std::sync::atomic::Ordering::Release,
             );
             self.local = self.local.saturating_sub(amount);
+            self.touch();
         }
     }
 
@@ -526,4 +961,268 @@ mod test {
             registry.read_counts::<Vec<_>>()
         );
     }
+
+    #[test]
+    fn evict_idle_removes_empty_category() {
+        let registry = new_registry::<Categories>();
+        let category_tracker = registry.category(Categories::Miscellaneous);
+        drop(category_tracker.track());
+
+        registry.evict_idle(std::time::Duration::ZERO);
+
+        assert_eq!(CountsVec::new(), registry.read_counts::<Vec<_>>());
+    }
+
+    #[test]
+    fn evict_idle_keeps_nonzero_category() {
+        let registry = new_registry::<Categories>();
+        let category_tracker = registry.category(Categories::Miscellaneous);
+        let _counter = category_tracker.track();
+
+        registry.evict_idle(std::time::Duration::ZERO);
+
+        assert_eq!(
+            vec![(Categories::Miscellaneous, 1)],
+            registry.read_counts::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn evict_idle_keeps_recently_touched_category() {
+        let registry = new_registry::<Categories>();
+        let category_tracker = registry.category(Categories::Miscellaneous);
+        drop(category_tracker.track());
+
+        registry.evict_idle(std::time::Duration::from_secs(3600));
+
+        assert_eq!(
+            vec![(Categories::Miscellaneous, 0)],
+            registry.read_counts::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn surviving_tracker_reregisters_after_eviction() {
+        let registry = new_registry::<Categories>();
+        let category_tracker = registry.category(Categories::Miscellaneous);
+        drop(category_tracker.track());
+
+        registry.evict_idle(std::time::Duration::ZERO);
+        assert_eq!(CountsVec::new(), registry.read_counts::<Vec<_>>());
+
+        let _counter = category_tracker.track();
+        assert_eq!(
+            vec![(Categories::Miscellaneous, 1)],
+            registry.read_counts::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn independent_trackers_for_the_same_id_converge_after_eviction() {
+        let registry = new_registry::<Categories>();
+        let first_tracker = registry.category(Categories::Miscellaneous);
+        drop(first_tracker.track());
+
+        registry.evict_idle(std::time::Duration::ZERO);
+        assert_eq!(CountsVec::new(), registry.read_counts::<Vec<_>>());
+
+        // A second, independent lookup re-creates the category before `first_tracker` gets a
+        // chance to re-register it itself.
+        let second_tracker = registry.category(Categories::Miscellaneous);
+        let _second_counter = second_tracker.track();
+        assert_eq!(
+            vec![(Categories::Miscellaneous, 1)],
+            registry.read_counts::<Vec<_>>()
+        );
+
+        // `first_tracker` is stale: it must adopt the category `second_tracker` already
+        // registered instead of silently incrementing an Arc nothing else can see.
+        let _first_counter = first_tracker.track();
+        assert_eq!(
+            vec![(Categories::Miscellaneous, 2)],
+            registry.read_counts::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn stale_tracker_adopts_generation_of_the_category_another_tracker_reregistered() {
+        let registry = new_registry::<Categories>();
+        let first_tracker = registry.category(Categories::Miscellaneous);
+        let first_counter = first_tracker.track();
+
+        drop(first_counter);
+        registry.evict_idle(std::time::Duration::ZERO);
+
+        // Someone else re-registers the category before `first_tracker` does.
+        let second_tracker = registry.category(Categories::Miscellaneous);
+        let second_counter = second_tracker.track();
+
+        // `first_tracker` re-registers by adopting the category `second_tracker` already
+        // created, so both guards end up current against the same generation - neither looks
+        // current against a category that's actually been superseded.
+        let first_counter = first_tracker.track();
+
+        assert_eq!(first_tracker.generation(), second_tracker.generation());
+        assert!(first_counter.is_current(&first_tracker));
+        assert!(second_counter.is_current(&second_tracker));
+    }
+
+    #[test]
+    fn generation_is_monotonic_across_repeated_eviction_with_fresh_lookups() {
+        let registry = new_registry::<Categories>();
+
+        // Each of these lookups is independent (no shared `Tracker`), and each only recreates
+        // the category after the previous incarnation was evicted - no races involved.
+        let first_tracker = registry.category(Categories::Miscellaneous);
+        assert_eq!(0, first_tracker.generation());
+        drop(first_tracker.track());
+        registry.evict_idle(std::time::Duration::ZERO);
+
+        let second_tracker = registry.category(Categories::Miscellaneous);
+        assert_eq!(1, second_tracker.generation());
+        drop(second_tracker.track());
+        registry.evict_idle(std::time::Duration::ZERO);
+
+        let third_tracker = registry.category(Categories::Miscellaneous);
+        assert_eq!(2, third_tracker.generation());
+
+        // The older trackers still refer to their own, long-gone incarnations.
+        assert_eq!(0, first_tracker.generation());
+        assert_eq!(1, second_tracker.generation());
+        assert_ne!(first_tracker.generation(), third_tracker.generation());
+        assert_ne!(second_tracker.generation(), third_tracker.generation());
+    }
+
+    #[test]
+    fn peak_does_not_carry_over_an_eviction_and_reregistration_cycle() {
+        let registry = new_registry::<Categories>();
+        let category_tracker = registry.category(Categories::SpecificOne);
+
+        {
+            let mut size = category_tracker.track_size(0);
+            size.add(100);
+            size.subtract(100);
+        }
+        assert_eq!(
+            vec![(Categories::SpecificOne, 100)],
+            registry.read_peaks::<Vec<_>>()
+        );
+
+        registry.evict_idle(std::time::Duration::ZERO);
+        assert_eq!(CountsVec::new(), registry.read_counts::<Vec<_>>());
+
+        // Re-registering through the very same `Tracker` starts a new incarnation of the
+        // category - its high-water-mark should not carry over from the one that was evicted,
+        // whether it's this `Tracker` or some other caller's `category()` lookup that wins the
+        // race to recreate it (see `fresh_category`).
+        let _size = category_tracker.track_size(0);
+        assert_eq!(
+            vec![(Categories::SpecificOne, 0)],
+            registry.read_peaks::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fresh_category_starts_at_generation_zero() {
+        let registry = new_registry::<Categories>();
+        let category_tracker = registry.category(Categories::Miscellaneous);
+
+        assert_eq!(0, category_tracker.generation());
+    }
+
+    #[test]
+    fn counter_is_current_until_category_is_evicted_and_reregistered() {
+        let registry = new_registry::<Categories>();
+        let category_tracker = registry.category(Categories::Miscellaneous);
+
+        let counter = category_tracker.track();
+        assert!(counter.is_current(&category_tracker));
+
+        drop(counter);
+        registry.evict_idle(std::time::Duration::ZERO);
+        let superseding_counter = category_tracker.track();
+
+        assert!(superseding_counter.is_current(&category_tracker));
+        assert_eq!(1, category_tracker.generation());
+    }
+
+    #[test]
+    fn size_generation_tracks_category_recreation() {
+        let registry = new_registry::<Categories>();
+        let category_tracker = registry.category(Categories::Miscellaneous);
+
+        let size = category_tracker.track_size(0);
+        assert!(size.is_current(&category_tracker));
+
+        drop(size);
+        registry.evict_idle(std::time::Duration::ZERO);
+        let superseding_size = category_tracker.track_size(0);
+
+        assert!(superseding_size.is_current(&category_tracker));
+        assert_eq!(1, category_tracker.generation());
+    }
+
+    #[test]
+    fn flush_every_exports_one_final_snapshot_on_drop() {
+        let registry = new_registry::<Categories>();
+        let _counter = registry.category(Categories::Miscellaneous).track();
+
+        let flushes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = flushes.clone();
+        let guard = registry.flush_every(
+            std::time::Duration::from_secs(3600),
+            move |snapshot: &[(Categories, usize)]| {
+                recorder.lock().expect("local lock").push(snapshot.to_vec());
+            },
+        );
+
+        drop(guard);
+
+        assert_eq!(
+            vec![vec![(Categories::Miscellaneous, 1)]],
+            *flushes.lock().expect("local lock")
+        );
+    }
+
+    #[test]
+    fn peak_tracks_the_high_water_mark_not_the_current_total() {
+        let registry = new_registry::<Categories>();
+        let category_tracker = registry.category(Categories::SpecificOne);
+
+        let mut size = category_tracker.track_size(4);
+        size.add(6);
+        size.subtract(7);
+
+        assert_eq!(
+            vec![(Categories::SpecificOne, 3, 10)],
+            registry.read_stats::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(Categories::SpecificOne, 10)],
+            registry.read_peaks::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn reset_peaks_rebases_to_the_current_total() {
+        let registry = new_registry::<Categories>();
+        let category_tracker = registry.category(Categories::SpecificOne);
+
+        let mut size = category_tracker.track_size(4);
+        size.add(6);
+        size.subtract(7);
+
+        registry.reset_peaks();
+
+        assert_eq!(
+            vec![(Categories::SpecificOne, 3)],
+            registry.read_peaks::<Vec<_>>()
+        );
+
+        size.add(1);
+        assert_eq!(
+            vec![(Categories::SpecificOne, 4)],
+            registry.read_peaks::<Vec<_>>()
+        );
+    }
 }